@@ -0,0 +1,290 @@
+//! Static reference/citation linting: walks `tex/`, cross-checks labels,
+//! refs, cites, and file targets, and reports problems the engine would
+//! otherwise bury in `main.log`.
+
+use crate::cite;
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    pub file: PathBuf,
+    pub line: usize,
+    pub message: String,
+}
+
+/// Walks `tex_dir`, collecting labels/refs/cites/targets with regexes
+/// (skipping `%`-commented lines), cross-checks them against `bib_path`
+/// and the filesystem, and returns every problem found.
+pub fn run(tex_dir: &Path, bib_path: &Path) -> Result<Vec<Issue>> {
+    let label_re = Regex::new(r"\\label\{([^}]+)\}").unwrap();
+    let ref_re = Regex::new(r"\\(?:ref|eqref|cref)\{([^}]+)\}").unwrap();
+    let cite_re = Regex::new(r"\\cite\{([^}]+)\}").unwrap();
+    let input_re = Regex::new(r"\\input\{([^}]+)\}").unwrap();
+    let includegraphics_re = Regex::new(r"\\includegraphics(?:\[[^\]]*\])?\{([^}]+)\}").unwrap();
+
+    let mut labels: HashMap<String, Vec<(PathBuf, usize)>> = HashMap::new();
+    let mut refs: Vec<(String, PathBuf, usize)> = Vec::new();
+    let mut cites: Vec<(String, PathBuf, usize)> = Vec::new();
+    let mut issues = Vec::new();
+
+    // `\input` paths resolve relative to tex_dir (the main file's directory),
+    // but `\includegraphics` paths like `figures/plot.png` (as generated by
+    // `add figure`) resolve relative to the project root, since that's the
+    // cwd cmd_build invokes the engine from.
+    let project_root = tex_dir.parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("."));
+
+    for entry in WalkDir::new(tex_dir).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() { continue; }
+        if entry.path().extension().map(|e| e != "tex").unwrap_or(true) { continue; }
+
+        let text = fs::read_to_string(entry.path())
+            .with_context(|| format!("read {}", entry.path().display()))?;
+
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            if line.trim_start().starts_with('%') { continue; }
+
+            for caps in label_re.captures_iter(line) {
+                labels.entry(caps[1].to_string()).or_default().push((entry.path().to_path_buf(), line_no));
+            }
+            for caps in ref_re.captures_iter(line) {
+                for key in caps[1].split(',') {
+                    refs.push((key.trim().to_string(), entry.path().to_path_buf(), line_no));
+                }
+            }
+            for caps in cite_re.captures_iter(line) {
+                for key in caps[1].split(',') {
+                    cites.push((key.trim().to_string(), entry.path().to_path_buf(), line_no));
+                }
+            }
+            for caps in input_re.captures_iter(line) {
+                let target = caps[1].trim();
+                if !target_exists(tex_dir, target, &["", ".tex"]) && !markdown_shim_exists(tex_dir, target) {
+                    issues.push(Issue {
+                        severity: Severity::Error,
+                        file: entry.path().to_path_buf(),
+                        line: line_no,
+                        message: format!("\\input target not found: {}", target),
+                    });
+                }
+            }
+            for caps in includegraphics_re.captures_iter(line) {
+                let target = caps[1].trim();
+                if !target_exists(&project_root, target, &["", ".pdf", ".png", ".jpg", ".jpeg", ".svg", ".eps"]) {
+                    issues.push(Issue {
+                        severity: Severity::Error,
+                        file: entry.path().to_path_buf(),
+                        line: line_no,
+                        message: format!("\\includegraphics target not found: {}", target),
+                    });
+                }
+            }
+        }
+    }
+
+    for (label, locations) in &labels {
+        if locations.len() > 1 {
+            for (file, line) in locations {
+                issues.push(Issue {
+                    severity: Severity::Error,
+                    file: file.clone(),
+                    line: *line,
+                    message: format!("duplicate label: {}", label),
+                });
+            }
+        }
+    }
+
+    let referenced: HashSet<&str> = refs.iter().map(|(k, _, _)| k.as_str()).collect();
+    for (label, locations) in &labels {
+        if !referenced.contains(label.as_str()) {
+            let (file, line) = &locations[0];
+            issues.push(Issue {
+                severity: Severity::Warning,
+                file: file.clone(),
+                line: *line,
+                message: format!("label defined but never referenced: {}", label),
+            });
+        }
+    }
+
+    for (key, file, line) in &refs {
+        if !labels.contains_key(key) {
+            issues.push(Issue {
+                severity: Severity::Error,
+                file: file.clone(),
+                line: *line,
+                message: format!("\\ref to undefined label: {}", key),
+            });
+        }
+    }
+
+    let bib_keys = cite::existing_keys(bib_path);
+    for (key, file, line) in &cites {
+        if !bib_keys.contains(key) {
+            issues.push(Issue {
+                severity: Severity::Error,
+                file: file.clone(),
+                line: *line,
+                message: format!("\\cite key not found in {}: {}", bib_path.display(), key),
+            });
+        }
+    }
+
+    issues.sort_by_key(|i| (i.file.clone(), i.line));
+    Ok(issues)
+}
+
+/// Checks whether `target` (as written in the `.tex` source) resolves to a
+/// file on disk relative to `base`, trying each of `extensions`.
+fn target_exists(base: &Path, target: &str, extensions: &[&str]) -> bool {
+    extensions.iter().any(|ext| {
+        let candidate = if ext.is_empty() {
+            base.join(target)
+        } else {
+            base.join(format!("{}{}", target, ext))
+        };
+        candidate.exists()
+    })
+}
+
+/// A `\input` target may point at a location `md::transpile_sections` only
+/// populates at build time (the shim `add section --format md` generates,
+/// e.g. `../build/sections/intro`), which doesn't exist yet on a fresh
+/// checkout since `build/` is gitignored. Treat it as satisfied if the
+/// Markdown source it's transpiled from is present, so `check` can run
+/// before the first build.
+fn markdown_shim_exists(tex_dir: &Path, target: &str) -> bool {
+    let stem = Path::new(target).file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    if stem.is_empty() {
+        return false;
+    }
+    tex_dir.join("sections").join(format!("{}.md", stem)).exists()
+}
+
+/// Prints each issue with its source location and returns the error count.
+pub fn print_issues(issues: &[Issue]) -> usize {
+    const RED: &str = "\x1b[31m";
+    const YELLOW: &str = "\x1b[33m";
+    const RESET: &str = "\x1b[0m";
+
+    let mut errors = 0;
+    for issue in issues {
+        let (color, label) = match issue.severity {
+            Severity::Error => {
+                errors += 1;
+                (RED, "error")
+            }
+            Severity::Warning => (YELLOW, "warning"),
+        };
+        println!("{color}{label}{RESET}: {}:{} — {}", issue.file.display(), issue.line, issue.message);
+    }
+    if issues.is_empty() {
+        println!("No issues found.");
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a throwaway project under the system temp dir with a
+    /// `tex/` tree and `bib/references.bib`, returning its root.
+    fn fixture(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("paperx_check_test_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("tex/sections")).unwrap();
+        fs::create_dir_all(root.join("bib")).unwrap();
+        fs::create_dir_all(root.join("figures")).unwrap();
+        fs::write(root.join("figures/plot.png"), b"not really a png").unwrap();
+        fs::write(root.join("bib/references.bib"), "@article{knuth1984,\n  title = {Literate Programming},\n}\n").unwrap();
+        root
+    }
+
+    #[test]
+    fn includegraphics_target_resolves_relative_to_project_root_not_tex_dir() {
+        // Regression test: `add figure` generates root-relative paths like
+        // `figures/plot.png`; check used to resolve these against tex_dir
+        // and falsely report every legitimately-included figure as missing.
+        let root = fixture("figures");
+        fs::write(
+            root.join("tex/sections/intro.tex"),
+            "\\includegraphics{figures/plot.png}\n",
+        )
+        .unwrap();
+
+        let issues = run(&root.join("tex"), &root.join("bib/references.bib")).unwrap();
+        fs::remove_dir_all(&root).ok();
+
+        assert!(
+            issues.iter().all(|i| !i.message.contains("includegraphics")),
+            "unexpected issues: {:?}",
+            issues
+        );
+    }
+
+    #[test]
+    fn reports_undefined_ref_missing_cite_and_duplicate_label() {
+        let root = fixture("issues");
+        fs::write(
+            root.join("tex/sections/intro.tex"),
+            "\\label{sec:intro}\n\\label{sec:intro}\n\\ref{sec:missing}\n\\cite{missing2020}\n",
+        )
+        .unwrap();
+
+        let issues = run(&root.join("tex"), &root.join("bib/references.bib")).unwrap();
+        fs::remove_dir_all(&root).ok();
+
+        assert!(issues.iter().any(|i| i.message.contains("duplicate label: sec:intro")));
+        assert!(issues.iter().any(|i| i.message.contains("\\ref to undefined label: sec:missing")));
+        assert!(issues.iter().any(|i| i.message.contains("\\cite key not found") && i.message.contains("missing2020")));
+    }
+
+    #[test]
+    fn input_to_unbuilt_markdown_shim_is_not_reported_missing() {
+        // Regression test: `add section --format md` writes a shim pointing
+        // at ../build/sections/<name>, which only exists after a build.
+        // check must accept it on a fresh checkout if tex/sections/<name>.md
+        // is present.
+        let root = fixture("md_shim");
+        fs::write(root.join("tex/sections/intro.md"), "# Introduction\n").unwrap();
+        fs::write(
+            root.join("tex/sections/intro.tex"),
+            "\\input{../build/sections/intro}\n",
+        )
+        .unwrap();
+
+        let issues = run(&root.join("tex"), &root.join("bib/references.bib")).unwrap();
+        fs::remove_dir_all(&root).ok();
+
+        assert!(
+            issues.iter().all(|i| !i.message.contains("\\input target not found")),
+            "unexpected issues: {:?}",
+            issues
+        );
+    }
+
+    #[test]
+    fn ignores_commented_out_lines() {
+        let root = fixture("comments");
+        fs::write(root.join("tex/sections/intro.tex"), "% \\cite{missing2020}\n").unwrap();
+
+        let issues = run(&root.join("tex"), &root.join("bib/references.bib")).unwrap();
+        fs::remove_dir_all(&root).ok();
+
+        assert!(issues.is_empty());
+    }
+}
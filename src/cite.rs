@@ -0,0 +1,261 @@
+//! Imports references into `bib/references.bib`, either from a local RIS
+//! file or by fetching BibTeX for a DOI, so authors don't have to hand-write
+//! entries.
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// A reference parsed from RIS, ready to be rendered as a BibTeX entry.
+#[derive(Debug, Default)]
+pub struct RisEntry {
+    pub entry_type: String,
+    pub authors: Vec<String>,
+    pub title: String,
+    pub year: String,
+    pub journal: String,
+    pub volume: String,
+    pub number: String,
+    pub pages: String,
+    pub doi: String,
+    pub url: String,
+}
+
+/// Parses RIS's tag-based line format: each line is `XY  - value`. An
+/// `ER  -` line terminates the record; only the first record is used.
+pub fn parse_ris(text: &str) -> Result<RisEntry> {
+    let tag_re = Regex::new(r"^([A-Z][A-Z0-9])\s*-\s*(.*)$").unwrap();
+    let mut entry = RisEntry::default();
+
+    for line in text.lines() {
+        let line = line.trim_end();
+        let Some(caps) = tag_re.captures(line) else { continue };
+        let tag = &caps[1];
+        let value = caps[2].trim();
+
+        match tag {
+            "TY" => {
+                entry.entry_type = match value {
+                    "JOUR" => "article",
+                    "BOOK" => "book",
+                    "CONF" | "CPAPER" => "inproceedings",
+                    _ => "misc",
+                }
+                .to_string();
+            }
+            "AU" | "A1" => entry.authors.push(value.to_string()),
+            "TI" | "T1" => entry.title = value.to_string(),
+            "PY" | "Y1" => {
+                let digits: String = value.chars().take_while(|c| c.is_ascii_digit()).collect();
+                if digits.len() >= 4 {
+                    entry.year = digits[..4].to_string();
+                }
+            }
+            "JO" | "JF" | "T2" => entry.journal = value.to_string(),
+            "VL" => entry.volume = value.to_string(),
+            "IS" => entry.number = value.to_string(),
+            "SP" => entry.pages = value.to_string(),
+            "EP" if !entry.pages.is_empty() => {
+                entry.pages = format!("{}--{}", entry.pages, value);
+            }
+            "DO" => entry.doi = value.to_string(),
+            "UR" => entry.url = value.to_string(),
+            "ER" => break,
+            _ => {}
+        }
+    }
+
+    if entry.entry_type.is_empty() {
+        return Err(anyhow!("RIS record is missing a TY (type) tag"));
+    }
+    Ok(entry)
+}
+
+/// Derives a cite key from the first author's surname and the year,
+/// appending `a`/`b`/... if that key is already taken.
+pub fn generate_key(first_author: &str, year: &str, existing: &HashSet<String>) -> String {
+    let surname = first_author
+        .split(',')
+        .next()
+        .unwrap_or(first_author)
+        .split_whitespace()
+        .last()
+        .unwrap_or(first_author)
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>();
+    dedupe_key(&format!("{}{}", surname, year), existing)
+}
+
+/// Appends `a`/`b`/... to `base` until it no longer collides with `existing`.
+fn dedupe_key(base: &str, existing: &HashSet<String>) -> String {
+    if !existing.contains(base) {
+        return base.to_string();
+    }
+    for suffix in 'a'..='z' {
+        let candidate = format!("{}{}", base, suffix);
+        if !existing.contains(&candidate) {
+            return candidate;
+        }
+    }
+    base.to_string()
+}
+
+impl RisEntry {
+    /// Renders this entry as a BibTeX entry string using `key`.
+    pub fn to_bibtex(&self, key: &str) -> String {
+        let mut fields = Vec::new();
+        if !self.authors.is_empty() {
+            fields.push(("author".to_string(), self.authors.join(" and ")));
+        }
+        if !self.title.is_empty() {
+            fields.push(("title".to_string(), self.title.clone()));
+        }
+        if !self.year.is_empty() {
+            fields.push(("year".to_string(), self.year.clone()));
+        }
+        if !self.journal.is_empty() {
+            let field_name = if self.entry_type == "inproceedings" { "booktitle" } else { "journal" };
+            fields.push((field_name.to_string(), self.journal.clone()));
+        }
+        if !self.volume.is_empty() {
+            fields.push(("volume".to_string(), self.volume.clone()));
+        }
+        if !self.number.is_empty() {
+            fields.push(("number".to_string(), self.number.clone()));
+        }
+        if !self.pages.is_empty() {
+            fields.push(("pages".to_string(), self.pages.clone()));
+        }
+        if !self.doi.is_empty() {
+            fields.push(("doi".to_string(), self.doi.clone()));
+        }
+        if !self.url.is_empty() {
+            fields.push(("url".to_string(), self.url.clone()));
+        }
+
+        let body = fields
+            .iter()
+            .map(|(k, v)| format!("  {} = {{{}}},", k, v))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!("@{}{{{},\n{}\n}}\n", self.entry_type, key, body)
+    }
+}
+
+/// Fetches a BibTeX rendering of `doi` from the DOI content-negotiation
+/// endpoint and returns it with its cite key normalized against `existing`.
+pub fn fetch_doi(doi: &str, existing: &HashSet<String>) -> Result<String> {
+    let client = reqwest::blocking::Client::new();
+    let raw = client
+        .get(format!("https://doi.org/{}", doi))
+        .header("Accept", "application/x-bibtex")
+        .send()
+        .context("fetch DOI citation")?
+        .error_for_status()
+        .context("DOI lookup failed")?
+        .text()
+        .context("read DOI response body")?;
+
+    let key_re = Regex::new(r"^(@\w+\{)([^,]+)(,)").unwrap();
+    let Some(caps) = key_re.captures(raw.trim_start()) else {
+        return Err(anyhow!("unexpected BibTeX returned for DOI {}", doi));
+    };
+    let fallback_key = caps[2].trim().to_string();
+    let key = dedupe_key(&fallback_key, existing);
+
+    Ok(key_re.replace(raw.trim_start(), format!("${{1}}{},", key)).to_string())
+}
+
+/// Appends `bibtex` to `bib/references.bib`, skipping it if an entry with
+/// the same key is already present.
+pub fn append_entry(bib_path: &Path, bibtex: &str, key: &str) -> Result<bool> {
+    let existing = fs::read_to_string(bib_path).unwrap_or_default();
+    let key_re = Regex::new(&format!(r"(?m)^@\w+\{{\s*{}\s*,", regex::escape(key))).unwrap();
+    if key_re.is_match(&existing) {
+        return Ok(false);
+    }
+
+    let mut updated = existing;
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    updated.push_str(bibtex);
+    if let Some(parent) = bib_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(bib_path, updated).with_context(|| format!("write {}", bib_path.display()))?;
+    Ok(true)
+}
+
+/// Collects the cite keys already present in `bib/references.bib`.
+pub fn existing_keys(bib_path: &Path) -> HashSet<String> {
+    let text = fs::read_to_string(bib_path).unwrap_or_default();
+    let key_re = Regex::new(r"(?m)^@\w+\{\s*([^,\s]+)\s*,").unwrap();
+    key_re
+        .captures_iter(&text)
+        .map(|c| c[1].to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_journal_article_ris_record() {
+        let ris = "\
+TY  - JOUR
+AU  - Lee, Jane
+AU  - Kim, Sam
+TI  - On Widgets
+PY  - 2019/03/01
+JO  - Journal of Widgets
+VL  - 12
+IS  - 3
+SP  - 100
+EP  - 110
+DO  - 10.1234/widgets
+UR  - https://example.com/widgets
+ER  -
+";
+        let entry = parse_ris(ris).unwrap();
+        assert_eq!(entry.entry_type, "article");
+        assert_eq!(entry.authors, vec!["Lee, Jane", "Kim, Sam"]);
+        assert_eq!(entry.title, "On Widgets");
+        assert_eq!(entry.year, "2019");
+        assert_eq!(entry.journal, "Journal of Widgets");
+        assert_eq!(entry.pages, "100--110");
+        assert_eq!(entry.doi, "10.1234/widgets");
+
+        let bibtex = entry.to_bibtex("lee2019");
+        assert!(bibtex.starts_with("@article{lee2019,"));
+        assert!(bibtex.contains("author = {Lee, Jane and Kim, Sam},"));
+    }
+
+    #[test]
+    fn generate_key_appends_suffix_on_collision() {
+        let mut existing = HashSet::new();
+        existing.insert("lee2019".to_string());
+        let key = generate_key("Lee, Jane", "2019", &existing);
+        assert_eq!(key, "lee2019a");
+    }
+
+    #[test]
+    fn dedupe_key_does_not_reinterpret_an_already_complete_key() {
+        // Regression test: fetch_doi used to route a whole fallback key like
+        // "Lee2019" back through generate_key as if it were an author name,
+        // which doubled the year suffix ("lee20192019"). dedupe_key must
+        // treat the fallback key as already-final and only add a suffix
+        // letter on an actual collision.
+        let existing = HashSet::new();
+        assert_eq!(dedupe_key("Lee2019", &existing), "Lee2019");
+
+        let mut existing = HashSet::new();
+        existing.insert("Lee2019".to_string());
+        assert_eq!(dedupe_key("Lee2019", &existing), "Lee2019a");
+    }
+}
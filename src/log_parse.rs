@@ -0,0 +1,228 @@
+//! Parses the `main.log` emitted by tectonic/latexmk/pdflatex into structured
+//! diagnostics, so `build`/`watch` can report editor-grade errors and
+//! warnings instead of a blunt pass/fail.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn location(&self) -> String {
+        match (&self.file, self.line) {
+            (Some(f), Some(l)) => format!("{}:{}", f, l),
+            (Some(f), None) => f.clone(),
+            _ => "?".to_string(),
+        }
+    }
+}
+
+/// Scans `log_path` line-by-line, tracking TeX's `(`/`)` file-stack
+/// convention to know which file is "current" at any point, and collects
+/// `! ...` errors, `LaTeX/Package Warning:` lines, and `Overfull`/
+/// `Underfull \hbox` lines as diagnostics. Only the first line of a
+/// warning is read: continuation lines of a multi-line warning are not
+/// part of its message, and an `on input line N` on one of them is not
+/// picked up.
+pub fn parse_log(log_path: &Path) -> Result<Vec<Diagnostic>> {
+    let text = fs::read_to_string(log_path)
+        .with_context(|| format!("read {}", log_path.display()))?;
+    let lines: Vec<&str> = text.lines().collect();
+
+    let line_no_re = Regex::new(r"^l\.(\d+)").unwrap();
+    let on_input_line_re = Regex::new(r"on input line (\d+)").unwrap();
+    let package_warning_re = Regex::new(r"^Package (\S+) Warning:\s*(.*)$").unwrap();
+    let hbox_re = Regex::new(r"^(Overfull|Underfull) \\hbox .*? at lines (\d+)--(\d+)").unwrap();
+
+    let mut stack: Vec<Option<String>> = Vec::new();
+    let mut diags = Vec::new();
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        update_file_stack(line, &mut stack);
+
+        if let Some(message) = line.strip_prefix("! ") {
+            let mut file = current_file(&stack);
+            let mut diag_line = None;
+            // The line number is reported a few lines below as `l.<N> ...`.
+            for look_ahead in lines.iter().skip(i + 1).take(6) {
+                if let Some(m) = line_no_re.captures(look_ahead) {
+                    diag_line = m[1].parse().ok();
+                    break;
+                }
+            }
+            if file.is_none() {
+                file = Some("?".to_string());
+            }
+            diags.push(Diagnostic {
+                severity: Severity::Error,
+                file,
+                line: diag_line,
+                message: message.trim().to_string(),
+            });
+        } else if let Some(rest) = line.strip_prefix("LaTeX Warning: ") {
+            diags.push(warning_diagnostic(rest, &stack, &on_input_line_re));
+        } else if let Some(caps) = package_warning_re.captures(line) {
+            let body = format!("{}: {}", &caps[1], &caps[2]);
+            diags.push(warning_diagnostic(&body, &stack, &on_input_line_re));
+        } else if let Some(caps) = hbox_re.captures(line) {
+            diags.push(Diagnostic {
+                severity: Severity::Warning,
+                file: current_file(&stack),
+                line: caps[2].parse().ok(),
+                message: line.trim().to_string(),
+            });
+        }
+
+        i += 1;
+    }
+
+    Ok(diags)
+}
+
+fn warning_diagnostic(body: &str, stack: &[Option<String>], on_input_line_re: &Regex) -> Diagnostic {
+    let line = on_input_line_re
+        .captures(body)
+        .and_then(|c| c[1].parse().ok());
+    let message = on_input_line_re.replace(body, "").trim_end_matches('.').trim().to_string();
+    Diagnostic {
+        severity: Severity::Warning,
+        file: current_file(stack),
+        line,
+        message,
+    }
+}
+
+/// The innermost file actually open on the stack — entries pushed for a
+/// parenthetical that didn't look like a file path (e.g. `(badness 10000)`)
+/// are `None` and are skipped rather than mistaken for the current file.
+fn current_file(stack: &[Option<String>]) -> Option<String> {
+    stack.iter().rev().find_map(|f| f.clone())
+}
+
+/// Applies one log line's worth of `(`/`)` tokens to the file stack. TeX logs
+/// interleave these with arbitrary other parenthesized text (hbox badness,
+/// page-output brackets, ...), so every `(` pushes a slot — `Some(path)` when
+/// it looks like a file, `None` otherwise — and every `)` pops one slot.
+/// This keeps pushes and pops balanced regardless of what's inside, so a
+/// non-file parenthetical can never pop a real file off the stack.
+fn update_file_stack(line: &str, stack: &mut Vec<Option<String>>) {
+    for (idx, ch) in line.char_indices() {
+        match ch {
+            '(' => {
+                let rest = &line[idx + 1..];
+                stack.push(leading_path(rest));
+            }
+            ')' => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the path-looking prefix of `s` (up to the next whitespace,
+/// `(`, or `)`), if it plausibly names a file TeX would have opened.
+fn leading_path(s: &str) -> Option<String> {
+    let end = s
+        .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .unwrap_or(s.len());
+    let candidate = &s[..end];
+    if candidate.is_empty() || !candidate.contains('.') {
+        return None;
+    }
+    Some(candidate.to_string())
+}
+
+/// Prints each diagnostic with color and a trailing "N errors, M warnings"
+/// summary line.
+pub fn print_diagnostics(diags: &[Diagnostic]) {
+    const RED: &str = "\x1b[31m";
+    const YELLOW: &str = "\x1b[33m";
+    const RESET: &str = "\x1b[0m";
+
+    let mut errors = 0;
+    let mut warnings = 0;
+    for d in diags {
+        let (color, label) = match d.severity {
+            Severity::Error => {
+                errors += 1;
+                (RED, "error")
+            }
+            Severity::Warning => {
+                warnings += 1;
+                (YELLOW, "warning")
+            }
+        };
+        println!("{color}{label}{RESET}: {} — {}", d.location(), d.message);
+    }
+    println!("{errors} errors, {warnings} warnings");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write as _;
+
+    fn write_temp_log(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("paperx_log_parse_test_{}_{}.log", std::process::id(), name));
+        let mut f = fs::File::create(&path).unwrap();
+        f.write_all(contents.as_bytes()).unwrap();
+        path
+    }
+
+    #[test]
+    fn hbox_warning_does_not_pop_the_enclosing_file_off_the_stack() {
+        // Regression test: `(badness 10000)` inside the Underfull \hbox line
+        // used to pop a real file off the stack because its matching `(`
+        // wasn't recognized as a push (it doesn't look like a path).
+        let log = "\
+(./tex/main.tex
+(./tex/sections/introduction.tex
+Underfull \\hbox (badness 10000) in paragraph at lines 45--47
+[1])
+! Undefined control sequence.
+l.50 \\foo
+";
+        let path = write_temp_log("hbox", log);
+        let diags = parse_log(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        let warning = diags.iter().find(|d| d.severity == Severity::Warning).expect("expected an hbox warning");
+        assert_eq!(warning.file.as_deref(), Some("./tex/sections/introduction.tex"));
+
+        // introduction.tex was properly closed by `[1])` before this error,
+        // so it should resolve to the enclosing main.tex, not `None`.
+        let error = diags.iter().find(|d| d.severity == Severity::Error).expect("expected an error diagnostic");
+        assert_eq!(error.file.as_deref(), Some("./tex/main.tex"));
+        assert_eq!(error.line, Some(50));
+    }
+
+    #[test]
+    fn parses_latex_warning_with_input_line() {
+        let log = "LaTeX Warning: Citation `knuth1984' undefined on input line 12.\n";
+        let path = write_temp_log("warning", log);
+        let diags = parse_log(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].severity, Severity::Warning);
+        assert_eq!(diags[0].line, Some(12));
+        assert!(diags[0].message.contains("Citation `knuth1984' undefined"));
+    }
+}
@@ -4,7 +4,10 @@ use notify::{recommended_watcher, RecursiveMode, Watcher};
 use open;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -13,6 +16,11 @@ use std::time::{Duration, Instant};
 use toml;
 use walkdir::WalkDir;
 
+mod check;
+mod cite;
+mod log_parse;
+mod md;
+
 #[derive(Parser, Debug)]
 #[command(name = "paperx", version, about = "Rust LaTeX paper toolkit")]
 struct Cli {
@@ -78,12 +86,20 @@ enum Commands {
 
     /// Remove build artifacts
     Clean {},
+
+    /// Lint references/citations/labels before compiling
+    Check {},
 }
 
 #[derive(Subcommand, Debug)]
 enum AddSub {
-    /// Create tex/sections/<name>.tex and include it from main.tex
-    Section { name: String },
+    /// Create tex/sections/<name>.tex (or .md) and include it from main.tex
+    Section {
+        name: String,
+        /// Author the section in Markdown instead of raw LaTeX
+        #[arg(long, value_enum, default_value_t = SectionFormat::Tex)]
+        format: SectionFormat,
+    },
     /// Copy figure to figures/ and print a LaTeX snippet to include it
     Figure {
         /// Path to an existing image (png/jpg/pdf/svg etc.)
@@ -95,11 +111,22 @@ enum AddSub {
         #[arg(long)]
         caption: Option<String>,
     },
+    /// Import a reference into bib/references.bib from a .ris file or a DOI
+    Cite {
+        /// Path to a local .ris file
+        ris: Option<String>,
+        /// Fetch citation metadata for this DOI instead of reading a file
+        #[arg(long)]
+        doi: Option<String>,
+    },
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
 enum Template { ArticleEn, LtjsJa }
 
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum SectionFormat { Tex, Md }
+
 #[derive(Copy, Clone, Debug, ValueEnum)]
 enum EnginePref { Tectonic, Latexmk, Pdflatex, Lualatex }
 
@@ -128,8 +155,9 @@ fn main() -> Result<()> {
             cmd_watch(engine, &outdir)?;
         }
         Commands::Add { sub } => match sub {
-            AddSub::Section { name } => cmd_add_section(&name)?,
+            AddSub::Section { name, format } => cmd_add_section(&name, format)?,
             AddSub::Figure { path, label, caption } => cmd_add_figure(&path, label.as_deref(), caption.as_deref())?,
+            AddSub::Cite { ris, doi } => cmd_add_cite(ris.as_deref(), doi.as_deref())?,
         },
         Commands::Open {} => {
             let pdf = default_pdf_path()?;
@@ -139,6 +167,9 @@ fn main() -> Result<()> {
             if Path::new("build").exists() { fs::remove_dir_all("build").ok(); }
             println!("Cleaned build/");
         }
+        Commands::Check {} => {
+            cmd_check()?;
+        }
     }
     Ok(())
 }
@@ -194,37 +225,47 @@ fn cmd_build(engine_pref: EnginePref, outdir: &str) -> Result<PathBuf> {
     if !main.exists() { return Err(anyhow!("Main tex not found: {}", cfg.main_tex)); }
 
     fs::create_dir_all(outdir)?;
+    md::transpile_sections().context("transpile Markdown sections")?;
     let engine = pick_engine(engine_pref)?;
     println!("Using engine: {}", engine);
 
     let pdf_path = Path::new(outdir).join("main.pdf");
 
-    match engine.as_str() {
+    let build_result = match engine.as_str() {
         "tectonic" => {
             // tectonic -X compile tex/main.tex --outdir build --keep-logs --keep-intermediates
             run(Command::new("tectonic")
                 .args(["-X","compile"])
                 .arg(&cfg.main_tex)
-                .args(["--outdir", outdir, "--keep-logs", "--keep-intermediates"]))?;
+                .args(["--outdir", outdir, "--keep-logs", "--keep-intermediates"]))
         }
         "latexmk" => {
             run(Command::new("latexmk")
                 .args(["-pdf","-interaction=nonstopmode"])
                 .arg(format!("-output-directory={}", outdir))
-                .arg(&cfg.main_tex))?;
+                .arg(&cfg.main_tex))
         }
         "pdflatex" => {
             run(Command::new("pdflatex")
                 .arg(format!("-output-directory={}", outdir))
-                .arg(&cfg.main_tex))?;
+                .arg(&cfg.main_tex))
         }
         "lualatex" => {
             run(Command::new("lualatex")
                 .arg(format!("-output-directory={}", outdir))
-                .arg(&cfg.main_tex))?;
+                .arg(&cfg.main_tex))
         }
         other => return Err(anyhow!("Unknown engine: {}", other)),
+    };
+
+    let log_path = Path::new(outdir).join("main.log");
+    if log_path.exists() {
+        match log_parse::parse_log(&log_path) {
+            Ok(diags) => log_parse::print_diagnostics(&diags),
+            Err(e) => eprintln!("warning: failed to parse {}: {e:#}", log_path.display()),
+        }
     }
+    build_result?;
 
     if !pdf_path.exists() {
         // latexmk places PDF alongside outdir/main.pdf; tectonic does too. If not, try fallback.
@@ -234,23 +275,75 @@ fn cmd_build(engine_pref: EnginePref, outdir: &str) -> Result<PathBuf> {
     Ok(pdf_path)
 }
 
+/// Tracked source kinds: `*.tex`, `*.bib`, Markdown-authored sections under
+/// `tex/sections/`, and images under `figures/`.
+fn is_tracked_path(path: &Path) -> bool {
+    if is_ignored_path(path) { return false; }
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("tex") | Some("bib") => true,
+        Some("md") => path.components().any(|c| c.as_os_str() == "sections"),
+        _ => path.components().any(|c| c.as_os_str() == "figures"),
+    }
+}
+
+/// Filters out build artifacts and common editor temp/swap files, which
+/// otherwise cause spurious rebuilds under a plain timestamp debounce.
+fn is_ignored_path(path: &Path) -> bool {
+    if path.components().any(|c| c.as_os_str() == "build") { return true; }
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    name.ends_with(".swp") || name.ends_with('~') || name == "4913"
+}
+
+fn hash_file(path: &Path) -> Option<u64> {
+    let bytes = fs::read(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
 fn cmd_watch(engine: EnginePref, outdir: &str) -> Result<()> {
-    let last = Arc::new(Mutex::new(Instant::now() - Duration::from_secs(10)));
-    let debounce = Duration::from_millis(400);
-    let outdir = outdir.to_string(); // Convert to owned String
-    let last_clone = last.clone();
+    let outdir = outdir.to_string();
+    let quiet_window = Duration::from_millis(400);
+
+    let hashes: Arc<Mutex<HashMap<PathBuf, u64>>> = Arc::new(Mutex::new(HashMap::new()));
+    {
+        let mut h = hashes.lock().unwrap();
+        for dir in ["tex", "bib", "figures"] {
+            for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+                if entry.file_type().is_file() && is_tracked_path(entry.path()) {
+                    if let Some(hash) = hash_file(entry.path()) {
+                        h.insert(entry.path().to_path_buf(), hash);
+                    }
+                }
+            }
+        }
+    }
+
+    // Real changes accumulate here until a quiet window passes with no new
+    // ones, so a burst of saves coalesces into a single rebuild.
+    let pending: Arc<Mutex<(Vec<PathBuf>, Instant)>> = Arc::new(Mutex::new((Vec::new(), Instant::now())));
 
+    let hashes_cb = hashes.clone();
+    let pending_cb = pending.clone();
     let mut w = recommended_watcher(move |res: notify::Result<notify::Event>| {
         match res {
-            Ok(_evt) => {
-                if let Ok(last_instant) = last_clone.lock() {
-                    if last_instant.elapsed() > debounce {
-                        drop(last_instant);
-                        if let Ok(mut last_instant) = last_clone.lock() {
-                            *last_instant = Instant::now();
-                        }
-                        println!("\nðŸ” Change detected. Rebuilding...");
-                        if let Err(e) = cmd_build(engine, &outdir) { eprintln!("build error: {e:#}"); }
+            Ok(evt) => {
+                for path in evt.paths {
+                    if !is_tracked_path(&path) { continue; }
+                    let new_hash = hash_file(&path);
+                    let mut h = hashes_cb.lock().unwrap();
+                    let changed = match (h.get(&path), new_hash) {
+                        (Some(old), Some(new)) => *old != new,
+                        (None, Some(_)) => true,
+                        (_, None) => false, // removed or unreadable; nothing to rebuild from
+                    };
+                    if let Some(new) = new_hash {
+                        h.insert(path.clone(), new);
+                    }
+                    if changed {
+                        let mut p = pending_cb.lock().unwrap();
+                        p.0.push(path);
+                        p.1 = Instant::now();
                     }
                 }
             }
@@ -264,16 +357,41 @@ fn cmd_watch(engine: EnginePref, outdir: &str) -> Result<()> {
         }
     }
 
-    println!("Watching tex/, bib/, figures/ â€” press Ctrl+C to stop.");
-    // Block forever
-    loop { std::thread::sleep(Duration::from_secs(3600)); }
+    println!("Watching tex/, bib/, figures/ — press Ctrl+C to stop.");
+    loop {
+        std::thread::sleep(Duration::from_millis(100));
+        let mut p = pending.lock().unwrap();
+        if p.0.is_empty() || p.1.elapsed() < quiet_window {
+            continue;
+        }
+        let mut changed: Vec<PathBuf> = p.0.drain(..).collect();
+        drop(p);
+        changed.sort();
+        changed.dedup();
+        let names = changed.iter().map(|c| c.display().to_string()).collect::<Vec<_>>().join(", ");
+        println!("\n🔁 Rebuilding ({names})...");
+        if let Err(e) = cmd_build(engine, &outdir) { eprintln!("build error: {e:#}"); }
+    }
 }
 
-fn cmd_add_section(name: &str) -> Result<()> {
+fn cmd_add_section(name: &str, format: SectionFormat) -> Result<()> {
     let path = Path::new("tex/sections").join(format!("{}.tex", name));
     if path.exists() { return Err(anyhow!("Section already exists: {}", path.display())); }
     fs::create_dir_all(path.parent().unwrap())?;
-    write(&path, &format!("% Section: {n}\n\\section{{{N}}}\nWrite here.\n", n=name, N = titleize(name)))?;
+
+    match format {
+        SectionFormat::Tex => {
+            write(&path, &format!("% Section: {n}\n\\section{{{N}}}\nWrite here.\n", n=name, N = titleize(name)))?;
+        }
+        SectionFormat::Md => {
+            let md_path = Path::new("tex/sections").join(format!("{}.md", name));
+            write(&md_path, &format!("# {}\n\nWrite here.\n", titleize(name)))?;
+            // Shim: the real content is transpiled from the .md at build time
+            // into md::TRANSPILED_SECTIONS_DIR, which this \input targets
+            // regardless of the --outdir a given build/watch uses for the PDF.
+            write(&path, &format!("% Section: {n} (transpiled from {n}.md)\n\\input{{../build/sections/{n}}}\n", n = name))?;
+        }
+    }
 
     // Append to main.tex after marker or before \end{document}
     let main_path = Path::new("tex/main.tex");
@@ -307,6 +425,49 @@ fn cmd_add_figure(src: &str, label: Option<&str>, caption: Option<&str>) -> Resu
     Ok(())
 }
 
+fn cmd_add_cite(ris: Option<&str>, doi: Option<&str>) -> Result<()> {
+    let bib_path = Path::new("bib/references.bib");
+    let existing = cite::existing_keys(bib_path);
+
+    if let Some(doi) = doi {
+        let bibtex = cite::fetch_doi(doi, &existing).with_context(|| format!("fetch DOI {}", doi))?;
+        let key_re = Regex::new(r"^@\w+\{([^,]+),").unwrap();
+        let key = key_re
+            .captures(&bibtex)
+            .map(|c| c[1].to_string())
+            .ok_or_else(|| anyhow!("could not determine cite key for DOI {}", doi))?;
+        if cite::append_entry(bib_path, &bibtex, &key)? {
+            println!("✅ Added {} to {}", key, bib_path.display());
+        } else {
+            println!("Already present: {}", key);
+        }
+        return Ok(());
+    }
+
+    let ris_path = ris.ok_or_else(|| anyhow!("provide a .ris file path or --doi"))?;
+    let text = fs::read_to_string(ris_path).with_context(|| format!("read {}", ris_path))?;
+    let entry = cite::parse_ris(&text)?;
+    let first_author = entry.authors.first().cloned().unwrap_or_else(|| "unknown".to_string());
+    let key = cite::generate_key(&first_author, &entry.year, &existing);
+    let bibtex = entry.to_bibtex(&key);
+
+    if cite::append_entry(bib_path, &bibtex, &key)? {
+        println!("✅ Added {} to {}", key, bib_path.display());
+    } else {
+        println!("Already present: {}", key);
+    }
+    Ok(())
+}
+
+fn cmd_check() -> Result<()> {
+    let issues = check::run(Path::new("tex"), Path::new("bib/references.bib"))?;
+    let errors = check::print_issues(&issues);
+    if errors > 0 {
+        return Err(anyhow!("{} error(s) found", errors));
+    }
+    Ok(())
+}
+
 fn read_config() -> Result<Config> {
     let s = fs::read_to_string("paperx.toml").context("read paperx.toml")?;
     Ok(toml::from_str(&s).context("parse paperx.toml")?)
@@ -373,4 +534,34 @@ const SECTION_INTRO: &str = r#"% paperx: example section\n\\section{Introduction
 
 const TEMPLATE_ARTICLE_EN: &str = r#"% !TEX TS-program = tectonic\n\\documentclass[11pt]{article}\n\\usepackage[a4paper,margin=1in]{geometry}\n\\usepackage{graphicx}\n\\usepackage{booktabs}\n\\usepackage{hyperref}\n\\usepackage{amsmath,amssymb}\n\\usepackage{siunitx}\n\\usepackage{authblk}\n\\usepackage[numbers]{natbib}\n\n\\title{${TITLE}}\n\\author[1]{${AUTHOR}}\n\\affil[1]{${AFFIL}}\n\n\\date{\\today}\n\n\\begin{document}\n\\maketitle\n\n\\begin{abstract}\n${ABSTRACT}\n\\end{abstract}\n\n\\textbf{Keywords:} ${KEYWORDS}\n\n% paperx:sections\n\n\\input{sections/introduction}\n\n\\bibliographystyle{plainnat}\n\\bibliography{../bib/references}\n\\end{document}\n"#;
 
-const TEMPLATE_LTJS_JA: &str = r#"% !TEX TS-program = lualatex\n\\documentclass[11pt]{ltjsarticle}\n\\usepackage[a4paper,margin=25mm]{geometry}\n\\usepackage{graphicx}\n\\usepackage{booktabs}\n\\usepackage{luatexja-fontspec}\n\\usepackage{hyperref}\n\\usepackage{amsmath,amssymb}\n\\usepackage{siunitx}\n\\usepackage[numbers]{natbib}\n\\setmainjfont{Noto Serif CJK JP}\n\n\\title{${TITLE}}\n\\author{${AUTHOR}}\\\\\\textit{${AFFIL}}\n\\date{\\today}\n\n\\begin{document}\n\\maketitle\n\n\\begin{abstract}\n${ABSTRACT}\n\\end{abstract}\n\n\\textbf{ã‚­ãƒ¼ãƒ¯ãƒ¼ãƒ‰:} ${KEYWORDS}\n\n% paperx:sections\n\n\\input{sections/introduction}\n\n\\bibliographystyle{plainnat}\n\\bibliography{../bib/references}\n\\end{document}\n"#;
\ No newline at end of file
+const TEMPLATE_LTJS_JA: &str = r#"% !TEX TS-program = lualatex\n\\documentclass[11pt]{ltjsarticle}\n\\usepackage[a4paper,margin=25mm]{geometry}\n\\usepackage{graphicx}\n\\usepackage{booktabs}\n\\usepackage{luatexja-fontspec}\n\\usepackage{hyperref}\n\\usepackage{amsmath,amssymb}\n\\usepackage{siunitx}\n\\usepackage[numbers]{natbib}\n\\setmainjfont{Noto Serif CJK JP}\n\n\\title{${TITLE}}\n\\author{${AUTHOR}}\\\\\\textit{${AFFIL}}\n\\date{\\today}\n\n\\begin{document}\n\\maketitle\n\n\\begin{abstract}\n${ABSTRACT}\n\\end{abstract}\n\n\\textbf{ã‚­ãƒ¼ãƒ¯ãƒ¼ãƒ‰:} ${KEYWORDS}\n\n% paperx:sections\n\n\\input{sections/introduction}\n\n\\bibliographystyle{plainnat}\n\\bibliography{../bib/references}\n\\end{document}\n"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_tex_bib_figures_and_markdown_sections() {
+        assert!(is_tracked_path(Path::new("tex/main.tex")));
+        assert!(is_tracked_path(Path::new("bib/references.bib")));
+        assert!(is_tracked_path(Path::new("figures/plot.png")));
+        assert!(is_tracked_path(Path::new("tex/sections/intro.md")));
+    }
+
+    #[test]
+    fn does_not_track_unrelated_or_loose_markdown_files() {
+        // Markdown is tracked only as a section source under `sections/`;
+        // other .md files, such as a project README, must not trigger rebuilds.
+        assert!(!is_tracked_path(Path::new("README.md")));
+        assert!(!is_tracked_path(Path::new("notes/plan.md")));
+    }
+
+    #[test]
+    fn ignores_build_artifacts_and_editor_temp_files() {
+        assert!(is_ignored_path(Path::new("build/main.log")));
+        assert!(is_ignored_path(Path::new("tex/main.tex.swp")));
+        assert!(is_ignored_path(Path::new("tex/main.tex~")));
+        assert!(is_ignored_path(Path::new("4913")));
+        assert!(!is_ignored_path(Path::new("tex/main.tex")));
+    }
+}
\ No newline at end of file
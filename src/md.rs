@@ -0,0 +1,183 @@
+//! Transpiles Markdown-authored sections (`tex/sections/*.md`) into LaTeX at
+//! build time, so collaborators who don't know LaTeX can still contribute
+//! sections.
+
+use anyhow::{Context, Result};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Parser, Tag, TagEnd};
+use std::fs;
+use std::path::Path;
+
+/// Converts a Markdown document into the LaTeX body it should transpile to.
+pub fn transpile(markdown: &str) -> String {
+    let mut out = String::new();
+    let mut in_code_block = false;
+    let mut code_is_raw_latex = false;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                let cmd = match level {
+                    HeadingLevel::H1 => "section",
+                    HeadingLevel::H2 => "subsection",
+                    _ => "subsubsection",
+                };
+                out.push_str(&format!("\\{}{{", cmd));
+            }
+            Event::End(TagEnd::Heading(_)) => out.push_str("}\n\n"),
+
+            Event::Start(Tag::Emphasis) => out.push_str("\\emph{"),
+            Event::End(TagEnd::Emphasis) => out.push('}'),
+            Event::Start(Tag::Strong) => out.push_str("\\textbf{"),
+            Event::End(TagEnd::Strong) => out.push('}'),
+
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                out.push_str(&format!("\\href{{{}}}{{", dest_url));
+            }
+            Event::End(TagEnd::Link) => out.push('}'),
+
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                out.push_str(&format!(
+                    "\\begin{{figure}}[t]\\centering\\includegraphics[width=0.9\\linewidth]{{{}}}\\caption{{",
+                    dest_url
+                ));
+            }
+            Event::End(TagEnd::Image) => out.push_str("}\\end{figure}\n\n"),
+
+            Event::Start(Tag::CodeBlock(kind)) => {
+                in_code_block = true;
+                code_is_raw_latex = matches!(&kind, CodeBlockKind::Fenced(lang) if lang.as_ref() == "latex");
+                if !code_is_raw_latex {
+                    out.push_str("\\begin{verbatim}\n");
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                if !code_is_raw_latex {
+                    out.push_str("\\end{verbatim}\n\n");
+                } else {
+                    out.push('\n');
+                }
+            }
+
+            Event::Start(Tag::Paragraph) => {}
+            Event::End(TagEnd::Paragraph) => out.push_str("\n\n"),
+
+            Event::Text(text) => {
+                if in_code_block {
+                    out.push_str(&text);
+                } else {
+                    out.push_str(&escape_latex(&text));
+                }
+            }
+            Event::Code(text) => out.push_str(&format!("\\texttt{{{}}}", escape_latex(&text))),
+            Event::SoftBreak | Event::HardBreak => out.push('\n'),
+
+            _ => {}
+        }
+    }
+
+    out
+}
+
+fn escape_latex(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            // Handled first/specially so it doesn't re-trigger escaping of
+            // the backslash this macro itself introduces.
+            '\\' => out.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '^' => out.push_str("\\textasciicircum{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Where transpiled sections land. This is intentionally independent of the
+/// `--outdir` a given `build`/`watch` invocation uses for the PDF, since the
+/// `\input` shim `add section --format md` generates is a static file and
+/// can't be rewritten per-invocation to track a changing outdir.
+const TRANSPILED_SECTIONS_DIR: &str = "build/sections";
+
+/// Transpiles every `tex/sections/*.md` file into `TRANSPILED_SECTIONS_DIR`,
+/// so the `\input` shims generated by `add section --format md` resolve.
+pub fn transpile_sections() -> Result<()> {
+    let sections_dir = Path::new("tex/sections");
+    if !sections_dir.exists() {
+        return Ok(());
+    }
+    let out_sections = Path::new(TRANSPILED_SECTIONS_DIR);
+
+    for entry in fs::read_dir(sections_dir).context("read tex/sections")? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().map(|e| e == "md").unwrap_or(false) {
+            let markdown = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+            let tex = transpile(&markdown);
+            let stem = path.file_stem().unwrap().to_string_lossy();
+            fs::create_dir_all(out_sections)?;
+            let out_path = out_sections.join(format!("{}.tex", stem));
+            fs::write(&out_path, tex).with_context(|| format!("write {}", out_path.display()))?;
+        }
+    }
+    Ok(())
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transpiles_headings_emphasis_links_and_images() {
+        let markdown = "\
+# Introduction
+
+This is *emphasized* and **strong**, see [the site](https://example.com)
+and this figure:
+
+![A plot](figures/plot.png)
+";
+        let tex = transpile(markdown);
+        assert!(tex.contains("\\section{Introduction}"));
+        assert!(tex.contains("\\emph{emphasized}"));
+        assert!(tex.contains("\\textbf{strong}"));
+        assert!(tex.contains("\\href{https://example.com}{the site}"));
+        assert!(tex.contains("\\includegraphics[width=0.9\\linewidth]{figures/plot.png}"));
+    }
+
+    #[test]
+    fn passes_through_raw_latex_fences_untouched() {
+        let markdown = "\
+```latex
+\\begin{equation} E = mc^2 \\end{equation}
+```
+";
+        let tex = transpile(markdown);
+        assert!(tex.contains("\\begin{equation} E = mc^2 \\end{equation}"));
+    }
+
+    #[test]
+    fn wraps_plain_fences_in_verbatim() {
+        let markdown = "```\nlet x = 1;\n```\n";
+        let tex = transpile(markdown);
+        assert!(tex.contains("\\begin{verbatim}\nlet x = 1;\n\\end{verbatim}"));
+    }
+
+    #[test]
+    fn escapes_special_characters_including_backslash() {
+        // Regression test: a literal backslash in prose (e.g. a Windows
+        // path or mention of a LaTeX command) used to pass through
+        // unescaped into the generated .tex.
+        let escaped = escape_latex("C:\\Users & 50% off ^ tilde~");
+        assert_eq!(
+            escaped,
+            "C:\\textbackslash{}Users \\& 50\\% off \\textasciicircum{} tilde\\textasciitilde{}"
+        );
+    }
+}